@@ -6,12 +6,22 @@ extern crate alloc;
 
 use axerrno::{ax_err, AxError, AxResult};
 use alloc::{string::String, sync::Arc};
-use axfs_vfs::{VfsNodeRef, VfsNodeType};
+use axfs_vfs::{VfsDirEntry, VfsNodeRef, VfsNodeType};
 use spinpreempt::SpinLock;
 use axfs_vfs::RootDirectory;
-use axtype::O_NOFOLLOW;
+use axtype::{O_NOFOLLOW, RENAME_EXCHANGE, RENAME_NOREPLACE};
 use lazy_init::LazyInit;
 use alloc::vec::Vec;
+use alloc::format;
+
+/// Maximum number of symlinks followed while resolving a single path, after
+/// which resolution is aborted with `ELOOP` (`AxError::TooManyLinks`).
+const MAX_SYMLINK_FOLLOWS: usize = 40;
+
+/// `access(2)`-style permission bits consulted by [`FsStruct::check_access`].
+pub const R_OK: u32 = 4;
+pub const W_OK: u32 = 2;
+pub const X_OK: u32 = 1;
 
 pub struct FsStruct {
     pub users: i32,
@@ -20,6 +30,21 @@ pub struct FsStruct {
     curr_dir: Option<VfsNodeRef>,
     root_dir: Option<Arc<RootDirectory>>,
     umask: u32,
+    /// `(canonical_mountpoint, fs_root, mount_count)`, ordered by insertion.
+    /// `mount_count` tracks how many other mounts are nested under this one,
+    /// so `unmount` can refuse to tear it down while busy (`remove_dir`
+    /// consults the table itself through `has_mounts_under`).
+    mounts: Vec<(String, VfsNodeRef, usize)>,
+}
+
+/// Options for [`FsStruct::copy`], mirroring the `copy_file`/`copy_dir`
+/// surface other `Fs` traits expose.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CopyOptions {
+    /// Overwrite `dst` if it already exists.
+    pub overwrite: bool,
+    /// Treat an already-existing `dst` as success instead of an error.
+    pub ignore_if_exists: bool,
 }
 
 impl FsStruct {
@@ -31,6 +56,7 @@ impl FsStruct {
             curr_dir: None,
             root_dir: None,
             umask: 0,
+            mounts: Vec::new(),
         }
     }
 
@@ -44,6 +70,45 @@ impl FsStruct {
         self.umask = mode;
     }
 
+    /// Strips the setuid bit, and the setgid bit when group-execute is
+    /// absent, from `mode`. Applied to the mode of every node created by
+    /// `create_file`/`create_dir`. Not applied by `create_symlink`, nor by
+    /// [`FsStruct::chmod`], which refuses non-owners and sets an owner's or
+    /// root's mode as given.
+    fn clear_suid_sgid(mode: i32) -> i32 {
+        const S_ISUID: i32 = 0o4000;
+        const S_ISGID: i32 = 0o2000;
+        const S_IXGRP: i32 = 0o010;
+        let mut mode = mode & !S_ISUID;
+        if mode & S_IXGRP == 0 {
+            mode &= !S_ISGID;
+        }
+        mode
+    }
+
+    /// Computes the mode a newly-created node should actually get: the
+    /// caller's requested `mode` masked by the stored `umask`, with
+    /// setuid/setgid cleared per [`Self::clear_suid_sgid`].
+    fn effective_create_mode(&self, mode: i32) -> i32 {
+        Self::clear_suid_sgid(mode & !(self.umask as i32))
+    }
+
+    /// `chmod`-style entry point: sets `path`'s mode to `mode`. Like
+    /// `chmod(2)`, only the node's owner or root may change its mode;
+    /// anyone else gets `PermissionDenied` regardless of write access to
+    /// the node's contents.
+    pub fn chmod(
+        &self, dir: Option<&VfsNodeRef>, path: &str, mode: i32,
+        uid: u32, gid: u32, groups: &[u32],
+    ) -> AxResult {
+        let node = self.lookup(dir, path, 0, uid, gid, groups)?;
+        let attr = node.get_attr()?;
+        if uid != 0 && uid != attr.uid() {
+            return ax_err!(PermissionDenied);
+        }
+        node.chmod(mode)
+    }
+
     pub fn copy_fs_struct(&mut self, fs: Arc<SpinLock<FsStruct>>) {
         let locked_fs = &fs.lock();
         self.root_dir = locked_fs.root_dir.as_ref().map(|root_dir| root_dir.clone());
@@ -60,12 +125,250 @@ impl FsStruct {
         }
     }
 
-    pub fn lookup(&self, dir: Option<&VfsNodeRef>, path: &str, flags: i32) -> AxResult<VfsNodeRef> {
+    /// Resolves the directory that actually contains the final component of
+    /// `path`, unlike `parent_node_of` which only returns the walk's
+    /// starting anchor (`root_dir`/`curr_dir`/`dir`) and ignores every
+    /// intermediate component. Used to run permission checks against the
+    /// real containing directory of a (possibly multi-component) path.
+    fn immediate_parent_of(
+        &self, dir: Option<&VfsNodeRef>, path: &str,
+        uid: u32, gid: u32, groups: &[u32],
+    ) -> AxResult<VfsNodeRef> {
+        let trimmed = path.trim_end_matches('/');
+        match trimmed.rfind('/') {
+            None | Some(0) => Ok(self.parent_node_of(dir, path)),
+            Some(idx) => self.lookup(dir, &trimmed[..idx], 0, uid, gid, groups),
+        }
+    }
+
+    /// Resolves the node and path that a mutating op (`create`/`remove`/
+    /// `link`/`symlink`/`rename`) should actually run against: `path`
+    /// canonicalized and checked against `self.mounts` the same way
+    /// `lookup` does, so crossing a mountpoint lands inside the mounted
+    /// filesystem's root instead of silently operating on the base
+    /// filesystem at the same literal path. Falls back to the plain
+    /// `parent_node_of`/`path` pair when `path` isn't under any mount, or
+    /// is relative to an explicit `dir`.
+    fn resolve_operation(&self, dir: Option<&VfsNodeRef>, path: &str) -> AxResult<(VfsNodeRef, String)> {
+        if dir.is_some() && !path.starts_with('/') {
+            return Ok((self.parent_node_of(dir, path), String::from(path)));
+        }
+        let abs = self.absolute_path(path)?;
+        match self.resolve_mount(&abs) {
+            Some((root, residual)) => Ok((root, residual)),
+            None => Ok((self.parent_node_of(dir, path), String::from(path))),
+        }
+    }
+
+    /// Finds the longest registered mountpoint that is a prefix of the
+    /// (already canonical) `abs_path` and returns that filesystem's root
+    /// together with the residual path inside it. Of several mounts on the
+    /// same mountpoint, the most recent one is used.
+    fn resolve_mount(&self, abs_path: &str) -> Option<(VfsNodeRef, String)> {
+        let mut best: Option<&(String, VfsNodeRef, usize)> = None;
+        for entry in &self.mounts {
+            let mp = entry.0.as_str();
+            let under = abs_path == mp || abs_path.starts_with(&format!("{}/", mp));
+            if under && best.map_or(true, |b| mp.len() >= b.0.len()) {
+                best = Some(entry);
+            }
+        }
+        best.map(|(mp, root, _)| {
+            let residual = abs_path[mp.len()..].trim_start_matches('/');
+            (root.clone(), String::from(residual))
+        })
+    }
+
+    /// Mounts `fs_root` at `mountpoint`; path resolution will transparently
+    /// descend into `fs_root` once it crosses `mountpoint`.
+    pub fn mount(&mut self, mountpoint: &str, fs_root: VfsNodeRef) -> AxResult {
+        let canonical = self.absolute_path(mountpoint)?;
+        let canonical = canonical.trim_end_matches('/').to_string();
+        for (path, _, count) in self.mounts.iter_mut() {
+            if canonical == *path || canonical.starts_with(&format!("{}/", path)) {
+                *count += 1;
+            }
+        }
+        let nested_prefix = format!("{}/", canonical);
+        let nested = self
+            .mounts
+            .iter()
+            .filter(|(path, _, _)| path.starts_with(&nested_prefix))
+            .count();
+        self.mounts.push((canonical, fs_root, nested));
+        Ok(())
+    }
+
+    /// Unmounts the filesystem mounted at `mountpoint`. Fails with `Busy` if
+    /// another mount is still nested on or under it.
+    pub fn unmount(&mut self, mountpoint: &str) -> AxResult {
+        let canonical = self.absolute_path(mountpoint)?;
+        let canonical = canonical.trim_end_matches('/').to_string();
+        let idx = self
+            .mounts
+            .iter()
+            .rposition(|(path, _, _)| *path == canonical)
+            .ok_or(AxError::NotFound)?;
+        if self.mounts[idx].2 > 0 {
+            return ax_err!(Busy);
+        }
+        self.mounts.remove(idx);
+        for (path, _, count) in self.mounts.iter_mut() {
+            if canonical == *path || canonical.starts_with(&format!("{}/", path)) {
+                *count -= 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `abs_path` is a mountpoint, or an ancestor of one, and so is
+    /// still busy and must not be unmounted or removed.
+    fn has_mounts_under(&self, abs_path: &str) -> bool {
+        let abs_path = abs_path.trim_end_matches('/');
+        self.mounts.iter().any(|(path, _, _)| {
+            path == abs_path || path.starts_with(&format!("{}/", abs_path))
+        })
+    }
+
+    /// POSIX owner/group/other access check. Picks the permission triad for
+    /// `node` based on whether `uid` is the node's owner, `gid` (or any of
+    /// `groups`) is its group, or neither, then verifies every bit set in
+    /// `mask` (some combination of [`R_OK`]/[`W_OK`]/[`X_OK`]) is granted.
+    /// `uid == 0` bypasses the check, except it still requires at least one
+    /// execute bit on anything but a directory when `mask` asks for `X_OK`.
+    pub fn check_access(
+        &self, node: &VfsNodeRef,
+        uid: u32, gid: u32, groups: &[u32],
+        mask: u32,
+    ) -> AxResult {
+        let attr = node.get_attr()?;
+        let perm = attr.perm();
+        if uid == 0 {
+            if mask & X_OK != 0
+                && !attr.is_dir()
+                && !(perm.owner_executable() || perm.group_executable() || perm.other_executable())
+            {
+                return ax_err!(PermissionDenied);
+            }
+            return Ok(());
+        }
+
+        let (readable, writable, executable) = if uid == attr.uid() {
+            (perm.owner_readable(), perm.owner_writable(), perm.owner_executable())
+        } else if gid == attr.gid() || groups.contains(&attr.gid()) {
+            (perm.group_readable(), perm.group_writable(), perm.group_executable())
+        } else {
+            (perm.other_readable(), perm.other_writable(), perm.other_executable())
+        };
+
+        if (mask & R_OK != 0 && !readable)
+            || (mask & W_OK != 0 && !writable)
+            || (mask & X_OK != 0 && !executable)
+        {
+            ax_err!(PermissionDenied)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Resolves `path` component by component, following symlinks as it goes.
+    ///
+    /// Starts from `root_dir` for an absolute path or from `dir`/`curr_dir`
+    /// for a relative one. Every time a non-final component turns out to be
+    /// a symlink, its target is read and canonicalized, and spliced in front
+    /// of the remaining components (an absolute target restarts the walk at
+    /// `root_dir`, a relative one keeps resolving from the directory the
+    /// symlink lives in). `follows` is capped at `MAX_SYMLINK_FOLLOWS` to
+    /// guard against symlink loops. The final component is only followed if
+    /// `O_NOFOLLOW` is not set in `flags`; otherwise the symlink node itself
+    /// is returned. `uid`/`gid`/`groups` are the caller's credentials; each
+    /// directory walked through is checked for search (`X_OK`) permission.
+    pub fn lookup(
+        &self, dir: Option<&VfsNodeRef>, path: &str, flags: i32,
+        uid: u32, gid: u32, groups: &[u32],
+    ) -> AxResult<VfsNodeRef> {
         if path.is_empty() {
             return ax_err!(NotFound);
         }
-        let (node, _) = self.parent_node_of(dir, path).lookup(path, flags)?;
-        if path.ends_with('/') && !node.get_attr()?.is_dir() {
+        let trailing_slash = path.ends_with('/');
+        let mut parent = self.parent_node_of(dir, path);
+        let mut components: Vec<String> = path
+            .trim_matches('/')
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect();
+
+        // Consult the mount table against the canonical absolute path, not
+        // the raw argument — otherwise a relative lookup, or an absolute one
+        // with `..`/redundant slashes, would silently miss a mountpoint.
+        // A path relative to an explicit `dir` has no known absolute form,
+        // so it is not matched against the mount table.
+        let abs_for_mount = self.absolute_path(path)?;
+        let mounted = if dir.is_none() || path.starts_with('/') {
+            self.resolve_mount(&abs_for_mount)
+        } else {
+            None
+        };
+        if let Some((root, residual)) = mounted {
+            parent = root;
+            components = residual
+                .split('/')
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect();
+        }
+
+        if components.is_empty() {
+            return Ok(parent);
+        }
+
+        let mut node = parent.clone();
+        let mut follows = 0usize;
+
+        while !components.is_empty() {
+            let name = components.remove(0);
+            let is_last = components.is_empty();
+            self.check_access(&parent, uid, gid, groups, X_OK)?;
+            let (next, _) = parent.lookup(&name, flags)?;
+
+            if is_last && (flags & O_NOFOLLOW != 0) {
+                node = next;
+                break;
+            }
+
+            if next.get_attr()?.file_type() == VfsNodeType::SymLink {
+                follows += 1;
+                if follows > MAX_SYMLINK_FOLLOWS {
+                    return ax_err!(TooManyLinks);
+                }
+                let target = axfs_vfs::path::canonicalize(&next.readlink()?);
+                let mut rest: Vec<String> = if target.starts_with('/') {
+                    if let Some((root, residual)) = self.resolve_mount(&target) {
+                        parent = root;
+                        residual.split('/').filter(|s| !s.is_empty()).map(String::from).collect()
+                    } else {
+                        parent = self.root_dir.clone().unwrap();
+                        target.trim_matches('/').split('/').filter(|s| !s.is_empty()).map(String::from).collect()
+                    }
+                } else {
+                    target.trim_matches('/').split('/').filter(|s| !s.is_empty()).map(String::from).collect()
+                };
+                rest.extend(components);
+                components = rest;
+                node = parent.clone();
+                continue;
+            }
+
+            if is_last {
+                node = next;
+            } else {
+                parent = next;
+                node = parent.clone();
+            }
+        }
+
+        if trailing_slash && !node.get_attr()?.is_dir() {
             ax_err!(NotADirectory)
         } else {
             Ok(node)
@@ -74,63 +377,76 @@ impl FsStruct {
 
     pub fn create_link(
         &self, dir: Option<&VfsNodeRef>,
-        path: &str, node: VfsNodeRef
+        path: &str, node: VfsNodeRef,
+        uid: u32, gid: u32, groups: &[u32],
     ) -> AxResult {
         if path.is_empty() {
             return ax_err!(NotFound);
         } else if path.ends_with('/') {
             return ax_err!(NotADirectory);
         }
-        let parent = self.parent_node_of(dir, path);
+        let access_parent = self.immediate_parent_of(dir, path, uid, gid, groups)?;
+        self.check_access(&access_parent, uid, gid, groups, W_OK)?;
+        let (parent, op_path) = self.resolve_operation(dir, path)?;
         info!("create_link: {}", path);
-        parent.link(path, node)
+        parent.link(&op_path, node)
     }
 
     pub fn create_symlink(
         &self, dir: Option<&VfsNodeRef>,
         path: &str, target: &str,
-        uid: u32, gid: u32, mode: i32
+        uid: u32, gid: u32, groups: &[u32], mode: i32
     ) -> AxResult {
         if path.is_empty() {
             return ax_err!(NotFound);
         } else if path.ends_with('/') {
             return ax_err!(NotADirectory);
         }
-        let parent = self.parent_node_of(dir, path);
+        let access_parent = self.immediate_parent_of(dir, path, uid, gid, groups)?;
+        self.check_access(&access_parent, uid, gid, groups, W_OK)?;
+        let (parent, op_path) = self.resolve_operation(dir, path)?;
         info!("create_symlink: {}", path);
-        parent.symlink(path, target, uid, gid, mode)
+        parent.symlink(&op_path, target, uid, gid, mode)
     }
 
-    pub fn create_file(&self, dir: Option<&VfsNodeRef>, path: &str, ty: VfsNodeType, uid: u32, gid: u32, mode: i32) -> AxResult<VfsNodeRef> {
+    pub fn create_file(
+        &self, dir: Option<&VfsNodeRef>, path: &str, ty: VfsNodeType,
+        uid: u32, gid: u32, groups: &[u32], mode: i32,
+    ) -> AxResult<VfsNodeRef> {
         info!("create_file: {} ..", path);
         if path.is_empty() {
             return ax_err!(NotFound);
         } else if path.ends_with('/') {
             return ax_err!(NotADirectory);
         }
-        let parent = self.parent_node_of(dir, path);
+        let access_parent = self.immediate_parent_of(dir, path, uid, gid, groups)?;
+        self.check_access(&access_parent, uid, gid, groups, W_OK)?;
         info!("create_file: step1");
-        parent.create(path, ty, uid, gid, mode)?;
-        let (node, _) = parent.lookup(path, 0)?;
+        let (parent, op_path) = self.resolve_operation(dir, path)?;
+        parent.create(&op_path, ty, uid, gid, self.effective_create_mode(mode))?;
+        let (node, _) = parent.lookup(&op_path, 0)?;
         Ok(node)
     }
 
-    pub fn create_dir(&self, dir: Option<&VfsNodeRef>, path: &str, uid: u32, gid: u32, mode: i32) -> AxResult {
+    pub fn create_dir(
+        &self, dir: Option<&VfsNodeRef>, path: &str,
+        uid: u32, gid: u32, groups: &[u32], mode: i32,
+    ) -> AxResult {
         if path.is_empty() {
             return ax_err!(InvalidInput);
         }
-    
-        if let Ok(_) = self.lookup(dir, path, 0) {
+
+        if let Ok(_) = self.lookup(dir, path, 0, uid, gid, groups) {
             return ax_err!(AlreadyExists);
         }
-    
+
         let components: Vec<&str> = path.trim_matches('/')
                                        .split('/')
                                        .filter(|s| !s.is_empty())
                                        .collect();
-                                    
+
         debug!("create_dir: {:?} ..", components);
-        
+
         if components.is_empty() {
             return ax_err!(InvalidInput);
         }
@@ -144,7 +460,7 @@ impl FsStruct {
 
         // 检查父目录
         if !parent_path.is_empty() {
-            match self.lookup(dir, &parent_path, 0) {
+            match self.lookup(dir, &parent_path, 0, uid, gid, groups) {
                 Ok(node) => {
                     // 确保是目录
                     if !node.get_attr()?.is_dir() {
@@ -154,15 +470,63 @@ impl FsStruct {
                 Err(_) => return ax_err!(NotFound), // 父目录不存在且非递归模式
             }
         }
-        
+
         // 在已存在的父目录下创建目标目录
-        match self.lookup(dir, path, 0) {
+        match self.lookup(dir, path, 0, uid, gid, groups) {
             Ok(_) => ax_err!(AlreadyExists),
-            Err(AxError::NotFound) => self.parent_node_of(dir, path).create(path, VfsNodeType::Dir, uid, gid, mode),
+            Err(AxError::NotFound) => {
+                let access_parent = self.immediate_parent_of(dir, path, uid, gid, groups)?;
+                self.check_access(&access_parent, uid, gid, groups, W_OK)?;
+                let (parent, op_path) = self.resolve_operation(dir, path)?;
+                parent.create(&op_path, VfsNodeType::Dir, uid, gid, self.effective_create_mode(mode))
+            }
             Err(e) => Err(e),
         }
     }
 
+    /// Recursive `mkdir -p`: creates every missing intermediate component of
+    /// `path` in order. A component that already exists as a directory is
+    /// treated as success; one that exists as something else is rejected
+    /// with `NotADirectory`.
+    pub fn create_dir_all(
+        &self, dir: Option<&VfsNodeRef>, path: &str,
+        uid: u32, gid: u32, groups: &[u32], mode: i32,
+    ) -> AxResult {
+        if path.is_empty() {
+            return ax_err!(InvalidInput);
+        }
+        let absolute = path.starts_with('/');
+        let components: Vec<&str> = path.trim_matches('/')
+                                       .split('/')
+                                       .filter(|s| !s.is_empty())
+                                       .collect();
+        if components.is_empty() {
+            return Ok(());
+        }
+
+        let mut partial = String::new();
+        for component in &components {
+            if !partial.is_empty() {
+                partial.push('/');
+            }
+            partial.push_str(component);
+            let sub_path = if absolute { format!("/{partial}") } else { partial.clone() };
+
+            match self.lookup(dir, &sub_path, 0, uid, gid, groups) {
+                Ok(node) => {
+                    if !node.get_attr()?.is_dir() {
+                        return ax_err!(NotADirectory);
+                    }
+                }
+                Err(AxError::NotFound) => {
+                    self.create_dir(dir, &sub_path, uid, gid, groups, mode)?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
     pub fn root_dir(&self) -> Option<Arc<RootDirectory>> {
         self.root_dir.clone()
     }
@@ -180,7 +544,7 @@ impl FsStruct {
         }
     }
 
-    pub fn set_current_dir(&mut self, path: &str) -> AxResult {
+    pub fn set_current_dir(&mut self, path: &str, uid: u32, gid: u32, groups: &[u32]) -> AxResult {
         let mut abs_path = self.absolute_path(path)?;
         if !abs_path.ends_with('/') {
             abs_path += "/";
@@ -191,30 +555,30 @@ impl FsStruct {
             return Ok(());
         }
 
-        let node = self.lookup(None, &abs_path, 0)?;
+        let node = self.lookup(None, &abs_path, 0, uid, gid, groups)?;
         let attr = node.get_attr()?;
         if !attr.is_dir() {
-            ax_err!(NotADirectory)
-        } else if !attr.perm().owner_executable() {
-            ax_err!(PermissionDenied)
-        } else {
-            self.curr_dir = Some(node);
-            self.curr_path = abs_path;
-            Ok(())
+            return ax_err!(NotADirectory);
         }
+        self.check_access(&node, uid, gid, groups, X_OK)?;
+        self.curr_dir = Some(node);
+        self.curr_path = abs_path;
+        Ok(())
     }
 
-    pub fn remove_file(&self, dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
-        let node = self.lookup(dir, path, O_NOFOLLOW)?;
+    pub fn remove_file(&self, dir: Option<&VfsNodeRef>, path: &str, uid: u32, gid: u32, groups: &[u32]) -> AxResult {
+        let node = self.lookup(dir, path, O_NOFOLLOW, uid, gid, groups)?;
         let attr = node.get_attr()?;
         if attr.is_dir() {
-            ax_err!(IsADirectory)
-        } else {
-            self.parent_node_of(dir, path).remove(path)
+            return ax_err!(IsADirectory);
         }
+        let access_parent = self.immediate_parent_of(dir, path, uid, gid, groups)?;
+        self.check_access(&access_parent, uid, gid, groups, W_OK)?;
+        let (parent, op_path) = self.resolve_operation(dir, path)?;
+        parent.remove(&op_path)
     }
 
-    pub fn remove_dir(&self, dir: Option<&VfsNodeRef>, path: &str) -> AxResult {
+    pub fn remove_dir(&self, dir: Option<&VfsNodeRef>, path: &str, uid: u32, gid: u32, groups: &[u32]) -> AxResult {
         if path.is_empty() {
             return ax_err!(NotFound);
         }
@@ -228,26 +592,209 @@ impl FsStruct {
         {
             return ax_err!(InvalidInput);
         }
-        if self.root_dir.as_ref().unwrap().contains(&self.absolute_path(path)?) {
+        let abs_path = self.absolute_path(path)?;
+        if self.root_dir.as_ref().unwrap().contains(&abs_path) {
             return ax_err!(PermissionDenied);
         }
+        if self.has_mounts_under(&abs_path) {
+            return ax_err!(Busy);
+        }
 
-        let node = self.lookup(dir, path, 0)?;
+        let node = self.lookup(dir, path, 0, uid, gid, groups)?;
         let attr = node.get_attr()?;
         if !attr.is_dir() {
-            ax_err!(NotADirectory)
-        } else if !attr.perm().owner_writable() {
-            ax_err!(PermissionDenied)
+            return ax_err!(NotADirectory);
+        }
+        self.check_access(&node, uid, gid, groups, W_OK)?;
+        let access_parent = self.immediate_parent_of(dir, path, uid, gid, groups)?;
+        self.check_access(&access_parent, uid, gid, groups, W_OK)?;
+        let (parent, op_path) = self.resolve_operation(dir, path)?;
+        parent.remove(&op_path)
+    }
+    /// Rejects `.`/`..` and a trailing slash onto a non-directory, the same
+    /// checks `renameat2` applies to both the source and destination.
+    fn check_rename_operand(&self, path: &str) -> AxResult {
+        let trimmed = path.trim_matches('/');
+        if trimmed.is_empty() || trimmed == "." || trimmed == ".." {
+            return ax_err!(InvalidInput);
+        }
+        if path.ends_with('/') {
+            match self.lookup(None, path, 0, 0, 0, &[]) {
+                Ok(node) if !node.get_attr()?.is_dir() => return ax_err!(NotADirectory),
+                Err(AxError::NotFound) => {}
+                Err(e) => return Err(e),
+                Ok(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn rename(&self, old: &str, new: &str, uid: u32, gid: u32, groups: &[u32]) -> AxResult {
+        self.rename2(old, new, 0, uid, gid, groups)
+    }
+
+    /// `renameat2`-style rename honoring `RENAME_NOREPLACE` and
+    /// `RENAME_EXCHANGE`. Requires `W_OK` on both `old`'s and `new`'s
+    /// containing directories, the same as `unlink`/`create` do. With no
+    /// flags this keeps the old clobber-on-rename behavior, removing an
+    /// existing `new` as `uid`/`gid`/`groups` (subject to the same `W_OK`
+    /// check `remove_file` always applies, rather than bypassing it as
+    /// root). `RENAME_NOREPLACE` fails instead of clobbering an existing
+    /// `new`. `RENAME_EXCHANGE` requires both `old` and `new` to already
+    /// exist; the underlying VFS has no atomic swap primitive, so this is
+    /// emulated as three renames through a temporary name that is checked
+    /// to be free first (not just guessed), with best-effort rollback if a
+    /// later step fails — not a true atomic exchange.
+    pub fn rename2(
+        &self, old: &str, new: &str, flags: u32,
+        uid: u32, gid: u32, groups: &[u32],
+    ) -> AxResult {
+        if flags & RENAME_NOREPLACE != 0 && flags & RENAME_EXCHANGE != 0 {
+            return ax_err!(InvalidInput);
+        }
+        self.check_rename_operand(old)?;
+        self.check_rename_operand(new)?;
+
+        let old_access_parent = self.immediate_parent_of(None, old, uid, gid, groups)?;
+        self.check_access(&old_access_parent, uid, gid, groups, W_OK)?;
+        let new_access_parent = self.immediate_parent_of(None, new, uid, gid, groups)?;
+        self.check_access(&new_access_parent, uid, gid, groups, W_OK)?;
+
+        let (old_parent, old_path) = self.resolve_operation(None, old)?;
+        let (new_parent, new_path) = self.resolve_operation(None, new)?;
+        let new_exists = new_parent.lookup(&new_path, 0).is_ok();
+
+        if flags & RENAME_EXCHANGE != 0 {
+            if old_parent.lookup(&old_path, 0).is_err() || !new_exists {
+                return ax_err!(NotFound);
+            }
+
+            let mut tmp = format!("{}.rename-exchange-tmp", old_path);
+            let mut suffix = 0u32;
+            while old_parent.lookup(&tmp, 0).is_ok() || new_parent.lookup(&tmp, 0).is_ok() {
+                suffix += 1;
+                if suffix > 1000 {
+                    return ax_err!(Busy);
+                }
+                tmp = format!("{}.rename-exchange-tmp.{}", old_path, suffix);
+            }
+
+            old_parent.rename(&old_path, &tmp)?;
+            if let Err(e) = new_parent.rename(&new_path, &old_path) {
+                let _ = old_parent.rename(&tmp, &old_path);
+                return Err(e);
+            }
+            if let Err(e) = old_parent.rename(&tmp, &new_path) {
+                let _ = new_parent.rename(&old_path, &new_path);
+                let _ = old_parent.rename(&tmp, &old_path);
+                return Err(e);
+            }
+            Ok(())
+        } else if flags & RENAME_NOREPLACE != 0 {
+            if new_exists {
+                return ax_err!(AlreadyExists);
+            }
+            old_parent.rename(&old_path, &new_path)
+        } else {
+            if new_exists {
+                warn!("dst file already exist, now remove it");
+                self.remove_file(None, new, uid, gid, groups)?;
+            }
+            old_parent.rename(&old_path, &new_path)
+        }
+    }
+
+    /// Copies `src` to `dst`. A regular file is streamed through the VFS
+    /// read/write ops into a freshly-created `dst`; a directory is
+    /// recursively recreated, copying every entry underneath it. `options`
+    /// controls whether an existing `dst` is overwritten, ignored, or
+    /// rejected with `AlreadyExists`.
+    pub fn copy(
+        &self, dir: Option<&VfsNodeRef>, src: &str, dst: &str,
+        options: CopyOptions, uid: u32, gid: u32, groups: &[u32],
+    ) -> AxResult {
+        let src_abs = self.absolute_path(src)?;
+        let dst_abs = self.absolute_path(dst)?;
+        let src_prefix = format!("{}/", src_abs.trim_end_matches('/'));
+        if dst_abs == src_abs || dst_abs.starts_with(&src_prefix) {
+            // Copying onto, or into, the source itself would overwrite data
+            // out from under the read loop or recurse into the copy forever.
+            return ax_err!(InvalidInput);
+        }
+
+        let src_node = self.lookup(dir, src, 0, uid, gid, groups)?;
+        let attr = src_node.get_attr()?;
+        let dst_exists = self.lookup(dir, dst, 0, uid, gid, groups).is_ok();
+
+        if dst_exists && !options.overwrite {
+            if options.ignore_if_exists {
+                return Ok(());
+            }
+            return ax_err!(AlreadyExists);
+        }
+
+        if attr.is_dir() {
+            self.copy_dir(dir, &src_node, src, dst, dst_exists, options, uid, gid, groups)
         } else {
-            self.parent_node_of(dir, path).remove(path)
+            self.check_access(&src_node, uid, gid, groups, R_OK)?;
+            self.copy_file(dir, &src_node, dst, dst_exists, attr.perm().bits() as i32, uid, gid, groups)
+        }
+    }
+
+    fn copy_file(
+        &self, dir: Option<&VfsNodeRef>, src_node: &VfsNodeRef, dst: &str,
+        dst_exists: bool, mode: i32, uid: u32, gid: u32, groups: &[u32],
+    ) -> AxResult {
+        if dst_exists {
+            // Reuse the same clobber-on-rename path the plain `rename` uses.
+            self.remove_file(dir, dst, uid, gid, groups)?;
         }
+        let dst_node = self.create_file(dir, dst, VfsNodeType::File, uid, gid, groups, mode)?;
+
+        let mut buf = [0u8; 4096];
+        let mut offset = 0u64;
+        loop {
+            let n = src_node.read_at(offset, &mut buf)?;
+            if n == 0 {
+                break;
+            }
+            dst_node.write_at(offset, &buf[..n])?;
+            offset += n as u64;
+        }
+        Ok(())
     }
-    pub fn rename(&self, old: &str, new: &str) -> AxResult {
-        if self.parent_node_of(None, new).lookup(new, 0).is_ok() {
-            warn!("dst file already exist, now remove it");
-            self.remove_file(None, new)?;
+
+    fn copy_dir(
+        &self, dir: Option<&VfsNodeRef>, src_node: &VfsNodeRef, src: &str, dst: &str,
+        dst_exists: bool, options: CopyOptions, uid: u32, gid: u32, groups: &[u32],
+    ) -> AxResult {
+        self.check_access(src_node, uid, gid, groups, R_OK | X_OK)?;
+        let mode = src_node.get_attr()?.perm().bits() as i32;
+        if !dst_exists {
+            self.create_dir(dir, dst, uid, gid, groups, mode)?;
+        }
+
+        let mut dirents: Vec<VfsDirEntry> = (0..32)
+            .map(|_| VfsDirEntry::new("", VfsNodeType::File))
+            .collect();
+        let mut start_idx = 0;
+        loop {
+            let n = src_node.read_dir(start_idx, &mut dirents)?;
+            if n == 0 {
+                break;
+            }
+            for entry in &dirents[..n] {
+                let name = entry.name();
+                if name == "." || name == ".." {
+                    continue;
+                }
+                let child_src = format!("{}/{}", src.trim_end_matches('/'), name);
+                let child_dst = format!("{}/{}", dst.trim_end_matches('/'), name);
+                self.copy(dir, &child_src, &child_dst, options, uid, gid, groups)?;
+            }
+            start_idx += n;
         }
-        self.parent_node_of(None, old).rename(old, new)
+        Ok(())
     }
 }
 